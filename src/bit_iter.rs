@@ -31,6 +31,57 @@ impl Iterator for IterBits {
     }
 }
 
+/// Reassemble an iterator of bits, as produced from pixel channel LSBs,
+/// back into bytes. Bits are consumed in the same order `BitIter` emits
+/// them, so `BitReader::new(byte.iter_bits())` round-trips a single byte.
+pub struct BitReader<I> {
+    bits: I,
+}
+
+impl<I: Iterator<Item = bool>> BitReader<I> {
+    pub fn new(bits: I) -> Self {
+        Self { bits }
+    }
+}
+
+impl<I: Iterator<Item = bool>> Iterator for BitReader<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut byte = 0;
+
+        for mask in BIT_MASK {
+            if self.bits.next()? {
+                byte |= mask;
+            }
+        }
+
+        Some(byte)
+    }
+}
+
+/// Read up to `depth` bits from `bits`, packing them into a byte with the
+/// first bit read as the lowest position, mirroring `BitReader`'s order.
+/// Stops early and zero-pads any bits that could not be read, but returns
+/// `None` if `bits` was already exhausted before a single bit was read.
+pub fn take_bits<I: Iterator<Item = bool>>(bits: &mut I, depth: u8) -> Option<u8> {
+    let mut value = 0;
+    let mut read_any = false;
+
+    for i in 0..depth {
+        match bits.next() {
+            Some(true) => {
+                read_any = true;
+                value |= 1 << i;
+            }
+            Some(false) => read_any = true,
+            None => break,
+        }
+    }
+
+    read_any.then_some(value)
+}
+
 #[test]
 fn test_iter_bits() {
     let bits: Vec<bool> = 7.iter_bits().collect();
@@ -41,3 +92,18 @@ fn test_iter_bits() {
         &[true, false, false, true, false, false, false, false]
     );
 }
+
+#[test]
+fn test_bit_reader_round_trip() {
+    let bytes: Vec<u8> = BitReader::new([7u8, 9, 255].iter().flat_map(|b| b.iter_bits())).collect();
+    assert_eq!(bytes, &[7, 9, 255]);
+}
+
+#[test]
+fn test_take_bits() {
+    let mut bits = 7.iter_bits();
+    assert_eq!(take_bits(&mut bits, 3), Some(7));
+    assert_eq!(take_bits(&mut bits, 3), Some(0));
+    assert_eq!(take_bits(&mut bits, 3), Some(0));
+    assert_eq!(take_bits(&mut bits, 3), None);
+}
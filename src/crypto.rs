@@ -0,0 +1,81 @@
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, OsRng},
+    ChaCha20Poly1305, Key, KeyInit,
+};
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Derive a 32-byte `ChaCha20-Poly1305` key from `password` and `salt` using Argon2id.
+fn derive_key(password: &str, salt: &[u8; SALT_LEN]) -> Key {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .expect("32 bytes is a valid Argon2id output length");
+
+    Key::from(key)
+}
+
+/// Encrypt `payload` with `ChaCha20-Poly1305`, deriving the key from `password`
+/// via Argon2id with a fresh random salt and nonce. Returns
+/// `salt ‖ nonce ‖ ciphertext` (the ciphertext includes the 16-byte Poly1305
+/// tag), ready to be hidden as-is.
+///
+/// # Panics
+///
+/// Panics if encryption fails, which cannot happen with a freshly generated
+/// nonce.
+#[must_use]
+pub fn encrypt(payload: &[u8], password: &str) -> Vec<u8> {
+    let mut salt = [0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let key = derive_key(password, &salt);
+    let cipher = ChaCha20Poly1305::new(&key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, payload)
+        .expect("encryption with a freshly generated nonce cannot fail");
+
+    let mut out = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend(ciphertext);
+    out
+}
+
+/// Decrypt a payload produced by [`encrypt`]. Returns `None` if `data` is too
+/// short to contain a salt and nonce, or if decryption fails because of a
+/// wrong password or corrupted data.
+#[must_use]
+pub fn decrypt(data: &[u8], password: &str) -> Option<Vec<u8>> {
+    if data.len() < SALT_LEN + NONCE_LEN {
+        return None;
+    }
+
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let salt: [u8; SALT_LEN] = salt.try_into().ok()?;
+    let key = derive_key(password, &salt);
+    let cipher = ChaCha20Poly1305::new(&key);
+
+    cipher.decrypt(nonce.into(), ciphertext).ok()
+}
+
+#[test]
+fn test_encrypt_decrypt_round_trip() {
+    let encrypted = encrypt(b"hello world", "correct horse battery staple");
+    assert_eq!(
+        decrypt(&encrypted, "correct horse battery staple"),
+        Some(b"hello world".to_vec())
+    );
+}
+
+#[test]
+fn test_decrypt_wrong_password_fails() {
+    let encrypted = encrypt(b"hello world", "correct horse battery staple");
+    assert_eq!(decrypt(&encrypted, "wrong password"), None);
+}
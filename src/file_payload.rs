@@ -0,0 +1,45 @@
+use crate::leb128;
+
+/// Pack `filename` and `content` into a single payload suitable for
+/// `hide_lsb`: the filename's length and bytes, followed by the content's
+/// length and bytes. This lets [`unpack`] recover the original file without
+/// relying on the outer LEB128 frame that `hide_lsb` already adds.
+#[must_use]
+pub fn pack(filename: &str, content: &[u8]) -> Vec<u8> {
+    let filename = filename.as_bytes();
+
+    let mut payload = leb128::encode(filename.len() as u64);
+    payload.extend_from_slice(filename);
+    payload.extend(leb128::encode(content.len() as u64));
+    payload.extend_from_slice(content);
+    payload
+}
+
+/// Reverse [`pack`], returning the original filename and content. Returns
+/// `None` if `data` is too short or malformed.
+#[must_use]
+pub fn unpack(data: &[u8]) -> Option<(String, Vec<u8>)> {
+    let (filename_len, offset) = leb128::decode(data)?;
+    let rest = data.get(offset..)?;
+
+    let filename_len = usize::try_from(filename_len).ok()?;
+    let filename = rest.get(..filename_len)?;
+    let rest = rest.get(filename_len..)?;
+
+    let (content_len, offset) = leb128::decode(rest)?;
+    let rest = rest.get(offset..)?;
+
+    let content_len = usize::try_from(content_len).ok()?;
+    let content = rest.get(..content_len)?;
+
+    Some((String::from_utf8(filename.to_vec()).ok()?, content.to_vec()))
+}
+
+#[test]
+fn test_pack_unpack_round_trip() {
+    let packed = pack("notes.txt", b"hello world");
+    assert_eq!(
+        unpack(&packed),
+        Some((String::from("notes.txt"), b"hello world".to_vec()))
+    );
+}
@@ -0,0 +1,52 @@
+/// Encode `value` as an unsigned LEB128 varint: 7 data bits per byte, low
+/// group first, with the high bit set on every byte but the last to mark
+/// continuation.
+#[must_use]
+pub fn encode(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    loop {
+        let mut group = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value != 0 {
+            group |= 0x80;
+        }
+
+        bytes.push(group);
+
+        if value == 0 {
+            return bytes;
+        }
+    }
+}
+
+/// Decode an unsigned LEB128 varint from the start of `data`, returning the
+/// value and the number of bytes it occupied. Reads at most 10 groups
+/// (enough for a full `u64`) to guard against corrupt data without looping
+/// forever; returns `None` if no terminating byte was found within that cap.
+#[must_use]
+pub fn decode(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0;
+    let mut shift = 0;
+
+    for (i, &group) in data.iter().enumerate().take(10) {
+        value |= u64::from(group & 0x7f) << shift;
+
+        if group & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+
+        shift += 7;
+    }
+
+    None
+}
+
+#[test]
+fn test_round_trip() {
+    for value in [0, 1, 127, 128, 300, u64::MAX] {
+        let encoded = encode(value);
+        assert_eq!(decode(&encoded), Some((value, encoded.len())));
+    }
+}
@@ -0,0 +1,281 @@
+#![deny(clippy::pedantic)]
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use image::{open, DynamicImage, GenericImage, GenericImageView, ImageFormat};
+
+use crate::bit_iter::{take_bits, BitIter, BitReader};
+
+pub mod bit_iter;
+pub mod crypto;
+pub mod file_payload;
+pub mod leb128;
+
+/// Configuration for [`hide`].
+pub struct HideConfig {
+    pub carrier: PathBuf,
+    pub payload: Vec<u8>,
+    pub output: PathBuf,
+    pub bits: u8,
+    pub password: Option<String>,
+}
+
+/// Configuration for [`reveal`].
+pub struct RevealConfig {
+    pub carrier: PathBuf,
+    pub password: Option<String>,
+}
+
+/// Errors that can occur while hiding or revealing a payload.
+#[derive(Debug)]
+pub enum StegError {
+    /// The carrier image has room for `available` bits but the payload needs `needed`.
+    InsufficientCapacity { needed: usize, available: usize },
+    /// `HideConfig::bits` was outside the supported `1..=4` range.
+    InvalidBitDepth(u8),
+    /// An I/O or image-decoding error occurred.
+    Io(io::Error),
+    /// The input or output path has no image format `image` recognizes.
+    UnsupportedFormat,
+    /// The payload could not be decrypted with the given password.
+    DecryptFailed,
+}
+
+impl fmt::Display for StegError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsufficientCapacity { needed, available } => write!(
+                f,
+                "payload needs {needed} bits but the carrier only has room for {available}"
+            ),
+            Self::InvalidBitDepth(bits) => {
+                write!(f, "bit depth must be between 1 and 4, got {bits}")
+            }
+            Self::Io(err) => write!(f, "I/O error: {err}"),
+            Self::UnsupportedFormat => write!(f, "unsupported or undetectable image format"),
+            Self::DecryptFailed => write!(f, "wrong password or corrupt data"),
+        }
+    }
+}
+
+impl std::error::Error for StegError {}
+
+impl From<io::Error> for StegError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<image::ImageError> for StegError {
+    fn from(err: image::ImageError) -> Self {
+        match err {
+            image::ImageError::IoError(err) => Self::Io(err),
+            image::ImageError::Unsupported(_) => Self::UnsupportedFormat,
+            other => Self::Io(io::Error::other(other.to_string())),
+        }
+    }
+}
+
+/// All pixel coordinates of `image`, in the row-major order `hide_lsb` and
+/// `reveal_lsb` walk them in.
+fn pixel_coords(image: &DynamicImage) -> impl Iterator<Item = (u32, u32)> {
+    let (width, height) = (image.width(), image.height());
+    (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+}
+
+/// Write `depth` bits from `bits` into each RGB channel of the pixels
+/// produced by `coords`, masking off the low `depth` bits of each channel
+/// first. Stops as soon as `bits` runs dry, leaving any remaining channels of
+/// that last pixel untouched; pixels not consumed here are left for a
+/// subsequent call, e.g. to switch to a different bit depth partway through.
+fn write_bits_at_depth(
+    image: &mut DynamicImage,
+    coords: &mut impl Iterator<Item = (u32, u32)>,
+    bits: &mut impl Iterator<Item = bool>,
+    depth: u8,
+) {
+    let mask = (1 << depth) - 1;
+
+    for (x, y) in coords {
+        let mut pixel = image.get_pixel(x, y);
+        let mut exhausted = false;
+
+        for idx in 0..3 {
+            if let Some(value) = take_bits(bits, depth) {
+                pixel.0[idx] = (pixel.0[idx] & !mask) | value;
+            } else {
+                exhausted = true;
+                break;
+            }
+        }
+
+        image.put_pixel(x, y, pixel);
+
+        if exhausted {
+            return;
+        }
+    }
+}
+
+/// Lazily yield the low `depth` bits of each RGB channel of the pixels
+/// produced by `coords`, in the same bit order `write_bits_at_depth` packs
+/// them in.
+fn read_bits_at_depth<'a>(
+    image: &'a DynamicImage,
+    coords: &'a mut (impl Iterator<Item = (u32, u32)> + 'a),
+    depth: u8,
+) -> impl Iterator<Item = bool> + 'a {
+    coords.flat_map(move |(x, y)| {
+        let pixel = image.get_pixel(x, y);
+        (0..3).flat_map(move |idx| {
+            let value = pixel.0[idx];
+            (0..depth).map(move |i| value & (1 << i) != 0)
+        })
+    })
+}
+
+/// Hide a message inside the `bits` least significant bits of each RGB-part
+/// of a pixel, for a capacity of `width * height * 3 * bits` bits.
+/// A one-byte header stores `bits` itself (always at a depth of 1, so
+/// `reveal_lsb` can recover it before it knows the real depth), followed by
+/// the message length as a LEB128 varint and then the message, both written
+/// at the chosen depth.
+fn hide_lsb(image: &mut DynamicImage, message: &[u8], bits: u8) -> Result<(), StegError> {
+    if !(1..=4).contains(&bits) {
+        return Err(StegError::InvalidBitDepth(bits));
+    }
+
+    let length_prefix = leb128::encode(message.len() as u64);
+
+    // The header fills 8 channels at a depth of 1, but its third pixel is
+    // consumed whole, so the payload starts after 3 pixels (9 channels).
+    let header_channels = 9;
+    let payload_bits = u8::BITS as usize * (length_prefix.len() + message.len());
+    let payload_channels = payload_bits.div_ceil(usize::from(bits));
+    let needed_channels = header_channels + payload_channels;
+    let available_channels = (image.width() * image.height() * 3) as usize;
+
+    if needed_channels > available_channels {
+        return Err(StegError::InsufficientCapacity {
+            needed: needed_channels * usize::from(bits),
+            available: available_channels * usize::from(bits),
+        });
+    }
+
+    let mut coords = pixel_coords(image);
+
+    let mut header_bits = bits.iter_bits();
+    write_bits_at_depth(image, &mut coords, &mut header_bits, 1);
+
+    let mut payload_bits = length_prefix
+        .iter()
+        .chain(message)
+        .flat_map(|byte| (*byte).iter_bits());
+    write_bits_at_depth(image, &mut coords, &mut payload_bits, bits);
+
+    Ok(())
+}
+
+/// Reveal a message hidden by `hide_lsb`. The bit depth and message length
+/// are auto-detected from the header and the LEB128 length prefix; the
+/// length prefix is read until a byte with a clear continuation bit is found
+/// (capped at 10 groups, which is enough to hold a full `u64`). The header
+/// byte comes from untrusted pixel data, so it is rejected (returning an
+/// empty payload) unless it falls in the `1..=4` range `hide_lsb` writes;
+/// otherwise it could be used as an out-of-range shift amount.
+fn reveal_lsb(image: &DynamicImage) -> Vec<u8> {
+    let mut coords = pixel_coords(image);
+
+    let header = BitReader::new(read_bits_at_depth(image, &mut coords, 1)).next();
+    let Some(bits) = header.filter(|bits| (1..=4).contains(bits)) else {
+        return Vec::new();
+    };
+
+    let mut bytes = BitReader::new(read_bits_at_depth(image, &mut coords, bits));
+
+    let mut length: u64 = 0;
+    let mut shift = 0;
+    for _ in 0..10 {
+        let Some(group) = bytes.next() else {
+            return Vec::new();
+        };
+
+        length |= u64::from(group & 0x7f) << shift;
+
+        if group & 0x80 == 0 {
+            break;
+        }
+
+        shift += 7;
+    }
+
+    bytes
+        .take(length.try_into().unwrap_or(usize::MAX))
+        .collect()
+}
+
+/// Hide `config.payload` inside `config.carrier`, optionally encrypting it
+/// with `config.password` first, and write the result to `config.output`.
+///
+/// # Errors
+///
+/// Returns [`StegError::UnsupportedFormat`] if `config.carrier` or
+/// `config.output` has no recognizable image format,
+/// [`StegError::InvalidBitDepth`] if `config.bits` is not in `1..=4`,
+/// [`StegError::InsufficientCapacity`] if the payload does not fit, and
+/// [`StegError::Io`] on any other I/O or decoding failure.
+pub fn hide(config: HideConfig) -> Result<(), StegError> {
+    let mut image = open(&config.carrier)?;
+
+    let payload = match &config.password {
+        Some(password) => crypto::encrypt(&config.payload, password),
+        None => config.payload,
+    };
+
+    hide_lsb(&mut image, &payload, config.bits)?;
+
+    let format =
+        ImageFormat::from_path(&config.output).map_err(|_| StegError::UnsupportedFormat)?;
+    image.save_with_format(&config.output, format)?;
+
+    Ok(())
+}
+
+/// Reveal the payload hidden in `config.carrier`, decrypting it with
+/// `config.password` if one was given.
+///
+/// # Errors
+///
+/// Returns an error if `config.carrier` cannot be opened or decoded, or if
+/// [`StegError::DecryptFailed`] when a password is given but does not match.
+pub fn reveal(config: &RevealConfig) -> Result<Vec<u8>, StegError> {
+    let image = open(&config.carrier)?;
+    let revealed = reveal_lsb(&image);
+
+    match &config.password {
+        Some(password) => crypto::decrypt(&revealed, password).ok_or(StegError::DecryptFailed),
+        None => Ok(revealed),
+    }
+}
+
+#[test]
+fn test_hide_reveal_round_trip_depth_1() {
+    let mut image = DynamicImage::new_rgb8(64, 64);
+    hide_lsb(&mut image, b"hello world", 1).unwrap();
+    assert_eq!(reveal_lsb(&image), b"hello world");
+}
+
+#[test]
+fn test_hide_reveal_round_trip_higher_depth() {
+    let mut image = DynamicImage::new_rgb8(16, 16);
+    hide_lsb(&mut image, b"hi", 4).unwrap();
+    assert_eq!(reveal_lsb(&image), b"hi");
+}
+
+#[test]
+fn test_hide_lsb_insufficient_capacity() {
+    let mut image = DynamicImage::new_rgb8(1, 1);
+    let err = hide_lsb(&mut image, b"far too much data to fit", 1).unwrap_err();
+    assert!(matches!(err, StegError::InsufficientCapacity { .. }));
+}
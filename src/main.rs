@@ -1,98 +1,45 @@
 #![feature(iter_intersperse)]
 #![deny(clippy::pedantic)]
-use image::{open, DynamicImage, GenericImage, GenericImageView, ImageFormat, ImageResult};
-
 use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
-use crate::bit_iter::BitIter;
-
-mod bit_iter;
-
-/// An 48-bit (16 pixel) suffix for any message. This allows for detecting
-/// where to stop reading.
-/// In order to make this as unlikely to match with actual data as possible,
-/// it uses high and low bits.
-const LSB_MESSAGE_SUFFIX: &[u8; 6] = &[u8::MAX, u8::MAX, u8::MIN, u8::MIN, u8::MAX, u8::MIN];
-
-/// Hide a message inside the least significant bit of each RGB-part
-/// of a pixel. This means we can store 3 bits per pixel.
-/// Bits are stored from high to low.
-fn hide_lsb(image: &mut DynamicImage, message: &[u8]) {
-    // Make sure we have enough space in the image to hide the message and the LSB suffix.
-    debug_assert!(
-        u8::BITS * (message.len() + LSB_MESSAGE_SUFFIX.len()) as u32
-            <= image.width() * image.height() * 3
-    );
-
-    // Create an iterator over all bits of the message.
-    let mut bits = message
-        .iter()
-        .chain(LSB_MESSAGE_SUFFIX)
-        .map(|byte| (*byte).iter_bits())
-        .flatten();
-    let mut any_was_none = false;
-
-    // Iterate the pixels of the image and merge the bits, if possible.
-    for y in 0..image.height() {
-        for x in 0..image.width() {
-            let mut pixel = image.get_pixel(x, y);
-
-            // Iterate the RGB channels and set the bits.
-            for idx in 0..3 {
-                if let Some(value) = bits.next() {
-                    if value {
-                        pixel.0[idx] |= 1;
-                    } else {
-                        pixel.0[idx] &= !1;
-                    }
-                } else {
-                    any_was_none = true;
-                    break;
-                }
-            }
+use steganography::{file_payload, hide, reveal, HideConfig, RevealConfig};
 
-            image.put_pixel(x, y, pixel);
+/// Pull a `--password VALUE` pair out of `args`, if present, removing both
+/// entries so the remaining words can be joined back into the message.
+/// Returns `Ok(None)` if the flag is absent, `Ok(Some(value))` if it was
+/// given with a value, and `Err(())` if `--password` was the last argument
+/// with no value following it.
+fn extract_password_flag(args: &mut Vec<String>) -> Result<Option<String>, ()> {
+    let Some(idx) = args.iter().position(|arg| arg == "--password") else {
+        return Ok(None);
+    };
+    args.remove(idx);
 
-            // Terminate early if there are no more bits to hide.
-            if any_was_none {
-                return;
-            }
-        }
+    if idx < args.len() {
+        Ok(Some(args.remove(idx)))
+    } else {
+        Err(())
     }
 }
 
-/// Reveal a message inside the least significant (8th) bit of each RGB-part
-/// of a pixel.
-/// Bits are stored from high to low.
-fn reveal_lsb(image: &DynamicImage) -> Vec<u8> {
-    let mut bytes = Vec::new();
-    let mut byte = 0;
-    let mut bits_read = 0;
-
-    for y in 0..image.height() {
-        for x in 0..image.width() {
-            let pixel = image.get_pixel(x, y);
-
-            for idx in 0..3 {
-                byte |= (pixel.0[idx] & 1) << bits_read;
-                bits_read += 1;
-
-                if bits_read == 8 {
-                    bytes.push(byte);
-                    byte = 0;
-                    bits_read = 0;
-
-                    // Check if we got a magic suffix
-                    if bytes.ends_with(LSB_MESSAGE_SUFFIX) {
-                        bytes.truncate(bytes.len() - LSB_MESSAGE_SUFFIX.len());
-                        return bytes;
-                    }
-                }
-            }
-        }
-    }
+/// Pull a `--bits N` pair out of `args`, if present, removing both entries.
+/// Returns `Ok(None)` if the flag is absent, `Ok(Some(value))` if it was
+/// given with a value, and `Err(())` if `--bits` was the last argument with
+/// no value following it.
+fn extract_bits_flag(args: &mut Vec<String>) -> Result<Option<String>, ()> {
+    let Some(idx) = args.iter().position(|arg| arg == "--bits") else {
+        return Ok(None);
+    };
+    args.remove(idx);
 
-    bytes
+    if idx < args.len() {
+        Ok(Some(args.remove(idx)))
+    } else {
+        Err(())
+    }
 }
 
 macro_rules! get_next_argument_or {
@@ -119,7 +66,183 @@ macro_rules! might_fail {
     };
 }
 
-fn main() -> ImageResult<()> {
+/// Parse `--bits N` out of `rest`, falling back to a depth of 1. Prints a
+/// message and returns `None` if the flag's value is missing, unparseable,
+/// or outside `1..=4`.
+fn parse_bits_flag(rest: &mut Vec<String>) -> Option<u8> {
+    let bits = match extract_bits_flag(rest) {
+        Ok(Some(bits)) => bits.parse().ok(),
+        Ok(None) => Some(1),
+        Err(()) => None,
+    };
+
+    match bits {
+        Some(bits) if (1..=4).contains(&bits) => Some(bits),
+        _ => {
+            println!("--bits must be a number from 1 to 4");
+            None
+        }
+    }
+}
+
+fn run_hide(program_name: &str, params: &mut env::Args) -> io::Result<()> {
+    let in_file = get_next_argument_or!(
+        params,
+        "No input file provided. Usage: `{program_name} hide [in_file] [out_file]`"
+    );
+    let out_file = get_next_argument_or!(
+        params,
+        "No output file provided. Usage: `{program_name} hide [in_file] [out_file]`"
+    );
+    let mut rest: Vec<String> = params.collect();
+    let password = might_fail!(
+        extract_password_flag(&mut rest),
+        "--password requires a value"
+    );
+    let Some(bits) = parse_bits_flag(&mut rest) else {
+        return Ok(());
+    };
+    let message: String = rest.into_iter().intersperse(String::from(" ")).collect();
+
+    let config = HideConfig {
+        carrier: PathBuf::from(in_file),
+        payload: message.into_bytes(),
+        output: PathBuf::from(out_file),
+        bits,
+        password,
+    };
+
+    if let Err(err) = hide(config) {
+        println!("{err}");
+    }
+
+    Ok(())
+}
+
+fn run_reveal(program_name: &str, params: &mut env::Args) -> io::Result<()> {
+    let in_file = get_next_argument_or!(
+        params,
+        "No input file provided. Usage: `{program_name} reveal [in_file]`"
+    );
+
+    let mut rest: Vec<String> = params.collect();
+    let password = might_fail!(
+        extract_password_flag(&mut rest),
+        "--password requires a value"
+    );
+
+    let config = RevealConfig {
+        carrier: PathBuf::from(in_file),
+        password,
+    };
+
+    match reveal(&config) {
+        Ok(value) => println!("{}", String::from_utf8_lossy(&value)),
+        Err(err) => println!("{err}"),
+    }
+
+    Ok(())
+}
+
+fn run_hide_file(program_name: &str, params: &mut env::Args) -> io::Result<()> {
+    let in_file = get_next_argument_or!(
+        params,
+        "No input file provided. Usage: `{program_name} hide-file [in_file] [out_file] [payload_file]`"
+    );
+    let out_file = get_next_argument_or!(
+        params,
+        "No output file provided. Usage: `{program_name} hide-file [in_file] [out_file] [payload_file]`"
+    );
+    let payload_file = get_next_argument_or!(
+        params,
+        "No payload file provided. Usage: `{program_name} hide-file [in_file] [out_file] [payload_file]`"
+    );
+
+    let mut rest: Vec<String> = params.collect();
+    let password = might_fail!(
+        extract_password_flag(&mut rest),
+        "--password requires a value"
+    );
+    let Some(bits) = parse_bits_flag(&mut rest) else {
+        return Ok(());
+    };
+
+    let filename = might_fail!(
+        Path::new(&payload_file)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(()),
+        "Payload file has no valid file name"
+    );
+    let content = might_fail!(fs::read(&payload_file), "Could not read payload file");
+
+    let config = HideConfig {
+        carrier: PathBuf::from(in_file),
+        payload: file_payload::pack(filename, &content),
+        output: PathBuf::from(out_file),
+        bits,
+        password,
+    };
+
+    if let Err(err) = hide(config) {
+        println!("{err}");
+    }
+
+    Ok(())
+}
+
+fn run_reveal_file(program_name: &str, params: &mut env::Args) -> io::Result<()> {
+    let in_file = get_next_argument_or!(
+        params,
+        "No input file provided. Usage: `{program_name} reveal-file [in_file] [out_dir]`"
+    );
+    let out_dir = get_next_argument_or!(
+        params,
+        "No output directory provided. Usage: `{program_name} reveal-file [in_file] [out_dir]`"
+    );
+
+    let mut rest: Vec<String> = params.collect();
+    let password = might_fail!(
+        extract_password_flag(&mut rest),
+        "--password requires a value"
+    );
+
+    let config = RevealConfig {
+        carrier: PathBuf::from(in_file),
+        password,
+    };
+
+    let message = match reveal(&config) {
+        Ok(message) => message,
+        Err(err) => {
+            println!("{err}");
+            return Ok(());
+        }
+    };
+
+    let Some((filename, content)) = file_payload::unpack(&message) else {
+        println!("Could not parse hidden file payload");
+        return Ok(());
+    };
+
+    // The filename comes from the revealed payload, which an attacker
+    // controls (e.g. a crafted carrier image), so only its final path
+    // component is used; leading directories of `..` or absolute paths are
+    // dropped, and a name with no final component (such as `..`) is rejected.
+    let Some(safe_filename) = Path::new(&filename).file_name() else {
+        println!("Hidden file payload has an unsafe file name");
+        return Ok(());
+    };
+
+    might_fail!(
+        fs::write(Path::new(&out_dir).join(safe_filename), content),
+        "Could not write output file"
+    );
+
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
     // Parse command line arguments
     let mut params = env::args();
 
@@ -134,46 +257,16 @@ fn main() -> ImageResult<()> {
 
 Commands:
     `help` - shows this message
-    `hide [in_file] [out_file] [message]` - hides a message in an image
-    `reveal [in_file]` - reveals a message in an image"
-            )
-        }
-        "hide" => {
-            let in_file = get_next_argument_or!(
-                params,
-                "No input file provided. Usage: `{program_name} hide [in_file] [out_file]`"
+    `hide [in_file] [out_file] [--bits n] [--password password] [message]` - hides a message in an image
+    `reveal [in_file] [--password password]` - reveals a message in an image
+    `hide-file [in_file] [out_file] [payload_file] [--bits n] [--password password]` - hides a file in an image
+    `reveal-file [in_file] [out_dir] [--password password]` - reveals a file hidden with `hide-file`"
             );
-            let out_file = get_next_argument_or!(
-                params,
-                "No output file provided. Usage: `{program_name} hide [in_file] [out_file]`"
-            );
-            let message: String = params.intersperse(String::from(" ")).collect();
-
-            let mut input = might_fail!(open(in_file), "Could not open input file");
-
-            hide_lsb(&mut input, message.as_bytes());
-
-            let format = might_fail!(
-                ImageFormat::from_path(&out_file),
-                "Image format for output file could not be determined"
-            );
-            might_fail!(
-                input.save_with_format(out_file, format),
-                "Could not save output file"
-            );
-        }
-        "reveal" => {
-            let in_file = get_next_argument_or!(
-                params,
-                "No input file provided. Usage: `{program_name} hide [in_file] [out_file]`"
-            );
-
-            let input = might_fail!(open(in_file), "Could not open input file");
-
-            let value = reveal_lsb(&input);
-
-            println!("{}", String::from_utf8_lossy(&value));
         }
+        "hide" => run_hide(&program_name, &mut params)?,
+        "reveal" => run_reveal(&program_name, &mut params)?,
+        "hide-file" => run_hide_file(&program_name, &mut params)?,
+        "reveal-file" => run_reveal_file(&program_name, &mut params)?,
         _ => println!("Unknown command, try `{program_name} help`"),
     }
 